@@ -4,35 +4,179 @@ export_test_reactor!(T);
 
 struct T;
 
-static mut STATE: Vec<String> = Vec::new();
+// Each instance of the guest gets its own `string-bag`, created lazily on
+// first use and held for the lifetime of the instance. The bag itself is a
+// host-implemented resource (its backing `Vec<String>` lives in the host's
+// `Table`, not guest memory), so this thread-local only ever holds the
+// handle to it - no `unsafe`, and no cross-talk between instances.
+thread_local! {
+    static BAG: StringBag = StringBag::new();
+}
+
+// Interpolate a leading `$VAR` against the environment and push the result
+// onto `bag`. Shared by `add_strings` and `read_strings_from` so that
+// strings get the same treatment no matter which import they arrived
+// through.
+fn add_interpolated(bag: &StringBag, s: &str) {
+    match s.split_once('$') {
+        Some((prefix, var)) if prefix.is_empty() => match std::env::var(var) {
+            Ok(val) => bag.add(&val),
+            Err(_) => bag.add("undefined"),
+        },
+        _ => bag.add(s),
+    }
+}
 
 impl TestReactor for T {
     fn add_strings(ss: Vec<String>) -> u32 {
-        for s in ss {
-            match s.split_once("$") {
-                Some((prefix, var)) if prefix.is_empty() => match std::env::var(var) {
-                    Ok(val) => unsafe { STATE.push(val) },
-                    Err(_) => unsafe { STATE.push("undefined".to_owned()) },
-                },
-                _ => unsafe { STATE.push(s) },
+        BAG.with(|bag| {
+            for s in ss {
+                add_interpolated(bag, &s);
             }
-        }
-        unsafe { STATE.len() as u32 }
+            bag.len()
+        })
     }
     fn get_strings() -> Vec<String> {
-        unsafe { STATE.clone() }
+        BAG.with(|bag| bag.get())
     }
 
     fn write_strings_to(o: OutputStream) -> Result<(), ()> {
+        let pollable = wasi::io::streams::subscribe_to_output_stream(o);
         for s in Self::get_strings() {
             let output = format!("{s}\n");
-            wasi::io::streams::write(o, output.as_bytes()).map_err(|_| ())?;
-
-            //std::thread::sleep(std::time::Duration::from_secs(1));
+            write_all(o, pollable, output.as_bytes())?;
         }
+        wasi::poll::poll::drop_pollable(pollable);
         Ok(())
     }
     fn pass_an_imported_record(stat: wasi::filesystem::filesystem::DescriptorStat) -> String {
         format!("{stat:?}")
     }
+
+    fn fetch_lines(url: String) -> Result<u32, String> {
+        let (scheme, authority, path_with_query) = split_url(&url)?;
+
+        let request = wasi::http::types::new_outgoing_request(
+            &wasi::http::types::Method::Get,
+            Some(&path_with_query),
+            Some(&scheme),
+            Some(&authority),
+            wasi::http::types::new_fields(&[]),
+        );
+
+        let future_response = wasi::http::outgoing_handler::handle(request, None)
+            .map_err(|e| format!("failed to send request: {e:?}"))?;
+
+        let response = loop {
+            if let Some(result) = wasi::http::types::future_incoming_response_get(future_response)
+            {
+                break result.map_err(|e| format!("request failed: {e:?}"))?;
+            }
+            let pollable = wasi::http::types::listen_to_future_incoming_response(future_response);
+            wasi::poll::poll::poll_oneoff(&[pollable]);
+        };
+
+        let status = wasi::http::types::incoming_response_status(response);
+        if status >= 400 {
+            return Err(format!("unexpected status code: {status}"));
+        }
+
+        let body = wasi::http::types::incoming_response_consume(response)
+            .map_err(|_| "failed to consume response body".to_owned())?;
+
+        Self::read_strings_from(body).map_err(|()| "error reading response body".to_owned())
+    }
+
+    fn read_strings_from(i: InputStream) -> Result<u32, ()> {
+        BAG.with(|bag| {
+            let mut pending = Vec::new();
+            let mut count = 0u32;
+            loop {
+                match wasi::io::streams::read(i, 4096) {
+                    Ok((chunk, status)) => {
+                        pending.extend_from_slice(&chunk);
+                        while let Some(ix) = pending.iter().position(|&b| b == b'\n') {
+                            let line = pending.drain(..=ix).collect::<Vec<u8>>();
+                            let line = String::from_utf8_lossy(&line[..line.len() - 1]);
+                            add_interpolated(bag, &line);
+                            count += 1;
+                        }
+                        if status == wasi::io::streams::StreamStatus::Ended {
+                            if !pending.is_empty() {
+                                let line = String::from_utf8_lossy(&pending);
+                                add_interpolated(bag, &line);
+                                count += 1;
+                            }
+                            break;
+                        }
+                    }
+                    Err(_) => return Err(()),
+                }
+            }
+            Ok(count)
+        })
+    }
+
+    fn dump_to_file(path: String) -> Result<u32, String> {
+        let preopens = wasi::cli_base::preopens::get_directories();
+        let (root, _) = preopens
+            .first()
+            .ok_or_else(|| "no preopened directory available".to_owned())?;
+
+        let descriptor = root
+            .open_at(
+                wasi::filesystem::filesystem::DescriptorFlags::empty(),
+                &path,
+                wasi::filesystem::filesystem::OFlags::CREATE | wasi::filesystem::filesystem::OFlags::TRUNC,
+                wasi::filesystem::filesystem::DescriptorFlags::WRITE,
+                wasi::filesystem::filesystem::Modes::READABLE | wasi::filesystem::filesystem::Modes::WRITEABLE,
+            )
+            .map_err(|e| format!("open-at failed: {e:?}"))?;
+
+        let output = descriptor
+            .write_via_stream(0)
+            .map_err(|e| format!("write-via-stream failed: {e:?}"))?;
+
+        let mut count = 0u32;
+        for s in Self::get_strings() {
+            let line = format!("{s}\n");
+            wasi::io::streams::write(output, line.as_bytes())
+                .map_err(|_| "error writing to file".to_owned())?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+}
+
+// Write `bytes` to `o` in full, blocking on `pollable` (subscribed to `o`)
+// whenever the stream isn't ready, and advancing only by however much `write`
+// actually accepted. This is the backpressure protocol `wasi:io/streams`
+// expects; a bare loop of `write` calls can fail or hang against a bounded
+// output stream, and assuming a short write took the whole buffer would
+// silently drop the rest.
+fn write_all(o: OutputStream, pollable: wasi::poll::poll::Pollable, mut bytes: &[u8]) -> Result<(), ()> {
+    while !bytes.is_empty() {
+        wasi::poll::poll::poll_oneoff(&[pollable]);
+
+        let n = wasi::io::streams::write(o, bytes).map_err(|_| ())?;
+        bytes = &bytes[n as usize..];
+    }
+    Ok(())
+}
+
+// Split a `http://host[:port]/path?query` url into the pieces the
+// `wasi:http/types` outgoing-request constructor expects.
+fn split_url(url: &str) -> Result<(wasi::http::types::Scheme, String, String), String> {
+    let (scheme, rest) = if let Some(rest) = url.strip_prefix("https://") {
+        (wasi::http::types::Scheme::Https, rest)
+    } else if let Some(rest) = url.strip_prefix("http://") {
+        (wasi::http::types::Scheme::Http, rest)
+    } else {
+        return Err(format!("unsupported url: {url}"));
+    };
+    match rest.find('/') {
+        Some(ix) => Ok((scheme, rest[..ix].to_owned(), rest[ix..].to_owned())),
+        None => Ok((scheme, rest.to_owned(), "/".to_owned())),
+    }
 }
@@ -10,6 +10,12 @@ use wasmtime_wasi::preview2::{
     self, wasi::clocks::wall_clock, wasi::filesystem::filesystem, Table, WasiCtx, WasiCtxBuilder,
     WasiView,
 };
+use wasmtime_wasi_http::{WasiHttpCtx, WasiHttpView};
+
+mod string_bag_ext;
+mod sync;
+
+use string_bag_ext::TableStringBagExt;
 
 wasmtime::component::bindgen!({
     path: "../wit",
@@ -24,6 +30,8 @@ wasmtime::component::bindgen!({
        "wasi:cli-base/stdin": preview2::wasi::cli_base::stdin,
        "wasi:cli-base/stdout": preview2::wasi::cli_base::stdout,
        "wasi:cli-base/stderr": preview2::wasi::cli_base::stderr,
+       "wasi:http/types": wasmtime_wasi_http::types,
+       "wasi:http/outgoing-handler": wasmtime_wasi_http::outgoing_handler,
     },
     ownership: Borrowing {
         duplicate_if_necessary: false
@@ -33,6 +41,7 @@ wasmtime::component::bindgen!({
 struct ReactorCtx {
     table: Table,
     wasi: WasiCtx,
+    http: WasiHttpCtx,
 }
 
 impl WasiView for ReactorCtx {
@@ -50,10 +59,53 @@ impl WasiView for ReactorCtx {
     }
 }
 
+impl WasiHttpView for ReactorCtx {
+    fn table(&mut self) -> &mut Table {
+        &mut self.table
+    }
+    fn ctx(&mut self) -> &mut WasiHttpCtx {
+        &mut self.http
+    }
+}
+
+// The world's own `string-bag` resource isn't backed by anything in
+// wasmtime-wasi, so unlike the wasi interfaces above, we implement its
+// `Host` trait ourselves; storage is shared with the sync bindings via
+// `string_bag_ext::TableStringBagExt`.
+impl string_bag::Host for ReactorCtx {
+    fn new(&mut self) -> Result<u32> {
+        Ok(self.table.push_string_bag()?)
+    }
+    fn add(&mut self, bag: u32, s: String) -> Result<()> {
+        self.table.get_string_bag_mut(bag)?.push(s);
+        Ok(())
+    }
+    fn get(&mut self, bag: u32) -> Result<Vec<String>> {
+        Ok(self.table.get_string_bag(bag)?.clone())
+    }
+    fn len(&mut self, bag: u32) -> Result<u32> {
+        Ok(self.table.get_string_bag(bag)?.len() as u32)
+    }
+    fn drop(&mut self, bag: u32) -> Result<()> {
+        self.table.delete::<Vec<String>>(bag)?;
+        Ok(())
+    }
+}
+
 #[derive(Parser, Debug)]
 struct Args {
     #[arg()]
     file: String,
+
+    /// Host directory to preopen as the guest's "/", so that
+    /// `wasi:cli-base/preopens` has something to hand back.
+    #[arg(long, default_value = ".")]
+    preopened_dir: String,
+
+    /// Drive the reactor through the synchronous bindings instead, with no
+    /// tokio runtime involved.
+    #[arg(long)]
+    sync: bool,
 }
 
 async fn setup(args: Args) -> Result<(Store<ReactorCtx>, TestReactor)> {
@@ -64,7 +116,11 @@ async fn setup(args: Args) -> Result<(Store<ReactorCtx>, TestReactor)> {
 
     let engine = Engine::new(&config)?;
 
-    let mut linker = Linker::new(&engine);
+    setup_with_engine(&engine, args).await
+}
+
+fn reactor_linker(engine: &Engine) -> Result<Linker<ReactorCtx>> {
+    let mut linker = Linker::new(engine);
 
     // All of the imports available to the world are provided by the wasmtime-wasi preview2
     // implementation:
@@ -80,15 +136,36 @@ async fn setup(args: Args) -> Result<(Store<ReactorCtx>, TestReactor)> {
     preview2::wasi::cli_base::stdout::add_to_linker(&mut linker, |x| x)?;
     preview2::wasi::cli_base::stderr::add_to_linker(&mut linker, |x| x)?;
 
+    // The reactor also imports `wasi:http/outgoing-handler`, so that a guest
+    // can combine filesystem/env access with the ability to issue outgoing
+    // requests. This mirrors how the wasi-http `proxy` world registers the
+    // same two interfaces, plus the clocks/io/poll interfaces they depend on.
+    wasmtime_wasi_http::types::add_to_linker(&mut linker, |x| x)?;
+    wasmtime_wasi_http::outgoing_handler::add_to_linker(&mut linker, |x| x)?;
+
+    // The world's own `string-bag` resource - its `Host` impl is above,
+    // rather than coming from wasmtime-wasi or wasmtime-wasi-http.
+    string_bag::add_to_linker(&mut linker, |x| x)?;
+
+    Ok(linker)
+}
+
+async fn setup_with_engine(engine: &Engine, args: Args) -> Result<(Store<ReactorCtx>, TestReactor)> {
+    let linker = reactor_linker(engine)?;
+
+    let preopen_dir = cap_std::fs::Dir::open_ambient_dir(&args.preopened_dir, cap_std::ambient_authority())?;
+
     let mut table = Table::new();
     let wasi = WasiCtxBuilder::new()
         .push_env("GOOD_DOG", "gussie")
         .push_env("POUTY_DOG", "willa")
+        .push_preopened_dir(preopen_dir, "/")
         .build(&mut table)?;
+    let http = WasiHttpCtx::new();
 
-    let mut store = Store::new(&engine, ReactorCtx { table, wasi });
+    let mut store = Store::new(engine, ReactorCtx { table, wasi, http });
 
-    let component = Component::from_file(&engine, args.file)?;
+    let component = Component::from_file(engine, args.file)?;
 
     let (reactor, _instance) =
         TestReactor::instantiate_async(&mut store, &component, &linker).await?;
@@ -118,6 +195,11 @@ async fn setup(args: Args) -> Result<(Store<ReactorCtx>, TestReactor)> {
 async fn main() -> Result<()> {
     let args = Args::parse();
 
+    if args.sync {
+        return sync::run(&args.file);
+    }
+
+    let file = args.file.clone();
     let (mut store, mut reactor) = setup(args).await?;
 
     // Show that integration with the WASI context is working - the guest will
@@ -132,6 +214,188 @@ async fn main() -> Result<()> {
     assert_eq!(contents, &["hello", "gussie"]);
 
     demo_async_output_stream(&mut store, &mut reactor).await?;
+    demo_memory_input_stream(&mut store, &mut reactor).await?;
+    demo_async_input_stream(&mut store, &mut reactor).await?;
+    demo_outgoing_http(&mut store, &mut reactor).await?;
+    demo_filesystem_output(&file).await?;
+    demo_multiple_instances(store.engine(), &file).await?;
+
+    Ok(())
+}
+
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+
+async fn demo_memory_input_stream(
+    store: &mut Store<ReactorCtx>,
+    reactor: &mut TestReactor,
+) -> Result<()> {
+    // Show that we can feed a resource type whose impls are defined in the
+    // `host` and `wasi-common` crate in as well as out.
+    let readpipe = preview2::pipe::MemoryInputPipe::new("hello\n$GOOD_DOG\n");
+    let table_ix = preview2::TableStreamExt::push_input_stream(
+        store.data_mut().table_mut(),
+        Box::new(readpipe),
+    )?;
+    let r = reactor.call_read_strings_from(&mut *store, table_ix).await?;
+    assert_eq!(r, Ok(2));
+
+    // The bag may already hold strings from earlier in `main`, so only
+    // check what this call just appended.
+    let contents = reactor.call_get_strings(&mut *store).await?;
+    assert_eq!(&contents[contents.len() - 2..], &["hello", "gussie"]);
+
+    Ok(())
+}
+
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+
+async fn demo_async_input_stream(
+    store: &mut Store<ReactorCtx>,
+    reactor: &mut TestReactor,
+) -> Result<()> {
+    let (mut client, server) = tokio::io::duplex(64);
+
+    tokio::spawn(async move {
+        use tokio::io::AsyncWriteExt;
+        client.write_all(b"hello\n$GOOD_DOG\n").await.unwrap();
+    });
+
+    let readpipe = preview2::AsyncReadStream::new(server);
+    let table_ix = preview2::TableStreamExt::push_input_stream(
+        store.data_mut().table_mut(),
+        Box::new(readpipe),
+    )?;
+    let r = reactor.call_read_strings_from(&mut *store, table_ix).await?;
+    assert_eq!(r, Ok(2));
+
+    // The bag may already hold strings from earlier in `main`, so only
+    // check what this call just appended.
+    let contents = reactor.call_get_strings(&mut *store).await?;
+    assert_eq!(&contents[contents.len() - 2..], &["hello", "gussie"]);
+
+    Ok(())
+}
+
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+
+async fn demo_filesystem_output(file: &str) -> Result<()> {
+    // Show that the reactor can write through a preopened directory, not
+    // just through standalone streams.
+    let dir = tempfile::tempdir()?;
+    let (mut store, mut reactor) = setup(Args {
+        file: file.to_owned(),
+        preopened_dir: dir.path().to_str().unwrap().to_owned(),
+        sync: false,
+    })
+    .await?;
+
+    reactor
+        .call_add_strings(&mut store, &["hello", "$GOOD_DOG"])
+        .await?;
+
+    let r = reactor
+        .call_dump_to_file(&mut store, "out.txt")
+        .await?;
+    assert_eq!(r, Ok(2));
+
+    let contents = std::fs::read_to_string(dir.path().join("out.txt"))?;
+    assert_eq!(contents, "hello\ngussie\n");
+
+    Ok(())
+}
+
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+
+async fn demo_outgoing_http(store: &mut Store<ReactorCtx>, reactor: &mut TestReactor) -> Result<()> {
+    // Show that the reactor can reach out over the network as well as
+    // through the filesystem/env imports: `fetch_lines` issues a real
+    // `wasi:http/outgoing-handler` request and folds each line of the
+    // response body into the reactor's `string-bag`. Served from a
+    // throwaway local listener rather than a live URL, so this doesn't
+    // depend on outside connectivity and always gets a 2xx body back.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+
+    tokio::spawn(async move {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buf = [0u8; 1024];
+        let _ = socket.read(&mut buf).await.unwrap();
+
+        let body = "line-one\nline-two\n";
+        let response =
+            format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}", body.len());
+        socket.write_all(response.as_bytes()).await.unwrap();
+    });
+
+    let r = reactor
+        .call_fetch_lines(&mut *store, &format!("http://{addr}/lines"))
+        .await?;
+    assert_eq!(r, Ok(2));
 
     Ok(())
 }
@@ -195,7 +459,12 @@ async fn demo_async_output_stream(
     store: &mut Store<ReactorCtx>,
     reactor: &mut TestReactor,
 ) -> Result<()> {
-    let (mut client, server) = tokio::io::duplex(64);
+    // Deliberately smaller than the total payload, now that
+    // `write_strings_to` waits on a `subscribe-to-output-stream` pollable
+    // before each write and advances only by the byte count `write` returns,
+    // instead of blasting writes at the stream. It does not query a
+    // `check-write` budget.
+    let (mut client, server) = tokio::io::duplex(4);
 
     tokio::spawn(async move {
         use tokio::io::AsyncReadExt;
@@ -284,3 +553,65 @@ async fn demo_wasi_structures(
     assert_eq!(expected, got);
     Ok(())
 }
+
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+//
+
+async fn demo_multiple_instances(engine: &Engine, file: &str) -> Result<()> {
+    // Show that each reactor instance's `string-bag` is independent: two
+    // `TestReactor`s instantiated into the *same* `Store` (so they share one
+    // `Table` and one `WasiCtx`) don't see each other's `add_strings` calls,
+    // even when their calls are interleaved. `Store` access is `&mut`-exclusive
+    // and `!Send` across an await point shared by two instances, so unlike
+    // the other demos this one can't just `tokio::spawn` each side onto its
+    // own task; instead the interleaving is driven by hand, one await at a
+    // time, on a single store.
+    let args = Args {
+        file: file.to_owned(),
+        preopened_dir: ".".to_owned(),
+        sync: false,
+    };
+
+    let (mut store, mut reactor_a) = setup_with_engine(engine, args).await?;
+
+    // A second instance of the same component, sharing `store`'s `Table` and
+    // `WasiCtx` instead of getting its own.
+    let linker = reactor_linker(engine)?;
+    let component = Component::from_file(engine, file)?;
+    let (reactor_b, _instance) =
+        TestReactor::instantiate_async(&mut store, &component, &linker).await?;
+
+    // Alternate: run `a`'s call to completion, then `b`'s, then read both
+    // back, so that if the two instances' string-bags were accidentally
+    // shared, `a`'s string would show up in `b`'s count (or both in the reads).
+    let r = reactor_a
+        .call_add_strings(&mut store, &["from-a"])
+        .await?;
+    assert_eq!(r, 1);
+    let r = reactor_b
+        .call_add_strings(&mut store, &["from-b-one", "from-b-two"])
+        .await?;
+    assert_eq!(r, 2);
+
+    let strings_a = reactor_a.call_get_strings(&mut store).await?;
+    let strings_b = reactor_b.call_get_strings(&mut store).await?;
+    assert_eq!(strings_a, &["from-a"]);
+    assert_eq!(strings_b, &["from-b-one", "from-b-two"]);
+
+    Ok(())
+}
@@ -0,0 +1,25 @@
+//! Host-side storage for the `string-bag` resource, shared by the async
+//! bindings in `main` and the sync bindings in `sync`. The world's own
+//! `string-bag` isn't backed by anything in wasmtime-wasi, so each bag is
+//! just a `Vec<String>` living in the store's `Table`, with one entry per
+//! guest-held handle.
+
+use wasmtime_wasi::preview2::{Table, TableError};
+
+pub trait TableStringBagExt {
+    fn push_string_bag(&mut self) -> Result<u32, TableError>;
+    fn get_string_bag(&self, bag: u32) -> Result<&Vec<String>, TableError>;
+    fn get_string_bag_mut(&mut self, bag: u32) -> Result<&mut Vec<String>, TableError>;
+}
+
+impl TableStringBagExt for Table {
+    fn push_string_bag(&mut self) -> Result<u32, TableError> {
+        self.push(Box::new(Vec::<String>::new()))
+    }
+    fn get_string_bag(&self, bag: u32) -> Result<&Vec<String>, TableError> {
+        self.get(bag)
+    }
+    fn get_string_bag_mut(&mut self, bag: u32) -> Result<&mut Vec<String>, TableError> {
+        self.get_mut(bag)
+    }
+}
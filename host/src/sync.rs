@@ -0,0 +1,157 @@
+//! A synchronous counterpart to the `async: true` bindings used by the rest
+//! of this crate, for embedders that don't want to pull in a tokio runtime
+//! just to drive the reactor. The world and the guest component are
+//! identical; only how the host calls into it differs.
+
+use anyhow::Result;
+use wasmtime::{
+    component::{Component, Linker},
+    Config, Engine, Store,
+};
+use wasmtime_wasi::preview2::{self, Table, WasiCtx, WasiCtxBuilder, WasiView};
+use wasmtime_wasi_http::{WasiHttpCtx, WasiHttpView};
+
+use crate::string_bag_ext::TableStringBagExt;
+
+wasmtime::component::bindgen!({
+    path: "../wit",
+    world: "test-reactor",
+    async: false,
+    with: {
+       "wasi:io/streams": preview2::wasi::io::streams,
+       "wasi:filesystem/filesystem": preview2::wasi::filesystem::filesystem,
+       "wasi:cli-base/environment": preview2::wasi::cli_base::environment,
+       "wasi:cli-base/preopens": preview2::wasi::cli_base::preopens,
+       "wasi:cli-base/exit": preview2::wasi::cli_base::exit,
+       "wasi:cli-base/stdin": preview2::wasi::cli_base::stdin,
+       "wasi:cli-base/stdout": preview2::wasi::cli_base::stdout,
+       "wasi:cli-base/stderr": preview2::wasi::cli_base::stderr,
+       "wasi:http/types": wasmtime_wasi_http::types,
+       "wasi:http/outgoing-handler": wasmtime_wasi_http::outgoing_handler,
+    },
+    ownership: Borrowing {
+        duplicate_if_necessary: false
+    }
+});
+
+struct SyncReactorCtx {
+    table: Table,
+    wasi: WasiCtx,
+    http: WasiHttpCtx,
+}
+
+impl WasiView for SyncReactorCtx {
+    fn table(&self) -> &Table {
+        &self.table
+    }
+    fn table_mut(&mut self) -> &mut Table {
+        &mut self.table
+    }
+    fn ctx(&self) -> &WasiCtx {
+        &self.wasi
+    }
+    fn ctx_mut(&mut self) -> &mut WasiCtx {
+        &mut self.wasi
+    }
+}
+
+impl WasiHttpView for SyncReactorCtx {
+    fn table(&mut self) -> &mut Table {
+        &mut self.table
+    }
+    fn ctx(&mut self) -> &mut WasiHttpCtx {
+        &mut self.http
+    }
+}
+
+// Same host-implemented `string-bag` resource as the async world in `main`;
+// storage is shared via `string_bag_ext::TableStringBagExt`, only this
+// `Host` impl is per-bindgen.
+impl string_bag::Host for SyncReactorCtx {
+    fn new(&mut self) -> Result<u32> {
+        Ok(self.table.push_string_bag()?)
+    }
+    fn add(&mut self, bag: u32, s: String) -> Result<()> {
+        self.table.get_string_bag_mut(bag)?.push(s);
+        Ok(())
+    }
+    fn get(&mut self, bag: u32) -> Result<Vec<String>> {
+        Ok(self.table.get_string_bag(bag)?.clone())
+    }
+    fn len(&mut self, bag: u32) -> Result<u32> {
+        Ok(self.table.get_string_bag(bag)?.len() as u32)
+    }
+    fn drop(&mut self, bag: u32) -> Result<()> {
+        self.table.delete::<Vec<String>>(bag)?;
+        Ok(())
+    }
+}
+
+fn setup(file: &str) -> Result<(Store<SyncReactorCtx>, TestReactor)> {
+    let mut config = Config::new();
+    config.wasm_backtrace_details(wasmtime::WasmBacktraceDetails::Enable);
+    config.wasm_component_model(true);
+
+    let engine = Engine::new(&config)?;
+
+    let mut linker = Linker::new(&engine);
+
+    // Same set of imports as the async host, but registered as their
+    // blocking counterparts: `add_to_linker` wraps host functions with
+    // `func_wrap_async` and requires `Config::async_support`, which this
+    // store never enables. The `_sync` variants wrap them with plain
+    // `func_wrap` and do their I/O on the calling thread instead.
+    preview2::wasi::poll::poll::add_to_linker_sync(&mut linker, |x| x)?;
+    preview2::wasi::io::streams::add_to_linker_sync(&mut linker, |x| x)?;
+    preview2::wasi::clocks::monotonic_clock::add_to_linker_sync(&mut linker, |x| x)?;
+    preview2::wasi::clocks::wall_clock::add_to_linker_sync(&mut linker, |x| x)?;
+    preview2::wasi::filesystem::filesystem::add_to_linker_sync(&mut linker, |x| x)?;
+    preview2::wasi::cli_base::environment::add_to_linker_sync(&mut linker, |x| x)?;
+    preview2::wasi::cli_base::preopens::add_to_linker_sync(&mut linker, |x| x)?;
+    preview2::wasi::cli_base::exit::add_to_linker_sync(&mut linker, |x| x)?;
+    preview2::wasi::cli_base::stdin::add_to_linker_sync(&mut linker, |x| x)?;
+    preview2::wasi::cli_base::stdout::add_to_linker_sync(&mut linker, |x| x)?;
+    preview2::wasi::cli_base::stderr::add_to_linker_sync(&mut linker, |x| x)?;
+    wasmtime_wasi_http::types::add_to_linker_sync(&mut linker, |x| x)?;
+    wasmtime_wasi_http::outgoing_handler::add_to_linker_sync(&mut linker, |x| x)?;
+    string_bag::add_to_linker(&mut linker, |x| x)?;
+
+    let mut table = Table::new();
+    let wasi = WasiCtxBuilder::new()
+        .push_env("GOOD_DOG", "gussie")
+        .push_env("POUTY_DOG", "willa")
+        .build(&mut table)?;
+    let http = WasiHttpCtx::new();
+
+    let mut store = Store::new(&engine, SyncReactorCtx { table, wasi, http });
+
+    let component = Component::from_file(&engine, file)?;
+
+    let (reactor, _instance) = TestReactor::instantiate(&mut store, &component, &linker)?;
+
+    Ok((store, reactor))
+}
+
+/// Runs the same `add_strings`/`get_strings`/`write_strings_to` assertions
+/// as the async `main`, but without a tokio runtime anywhere in the stack.
+pub fn run(file: &str) -> Result<()> {
+    let (mut store, reactor) = setup(file)?;
+
+    let r = reactor.call_add_strings(&mut store, &["hello", "$GOOD_DOG"])?;
+    assert_eq!(r, 2);
+
+    let contents = reactor.call_get_strings(&mut store)?;
+    println!("call_get_strings (sync): {contents:?}");
+    assert_eq!(contents, &["hello", "gussie"]);
+
+    let writepipe = preview2::pipe::MemoryOutputPipe::new();
+    let table_ix = preview2::TableStreamExt::push_output_stream(
+        store.data_mut().table_mut(),
+        Box::new(writepipe.clone()),
+    )?;
+    let r = reactor.call_write_strings_to(&mut store, table_ix)?;
+    assert_eq!(r, Ok(()));
+    assert_eq!(writepipe.contents(), b"hello\ngussie\n");
+
+    Ok(())
+}